@@ -0,0 +1,7 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure()
+        .build_server(true)
+        .build_client(false)
+        .compile(&["proto/store/v1/message_state.proto"], &["proto"])?;
+    Ok(())
+}
@@ -0,0 +1,306 @@
+use {
+    super::{
+        proof::wormhole_merkle::construct_update_data,
+        types::{
+            MessageIdentifier,
+            MessageState,
+            MessageStateWithUpdateData,
+            MessageType,
+            RequestTime,
+            TwapsWithUpdateData,
+        },
+    },
+    anyhow::{
+        anyhow,
+        Result,
+    },
+    pyth_oracle::Message,
+    pyth_sdk::PriceIdentifier,
+};
+
+/// Returns the up-to-date `TwapMessage`s for `price_ids`, together with the Wormhole Merkle
+/// update data needed to verify them on-chain.
+///
+/// Mirrors `get_price_feeds_with_update_data`, but selects `MessageState`s of type
+/// `MessageType::TwapMessage` instead of `MessageType::PriceFeedMessage`.
+///
+/// `RequestTime::Range` is not meaningful here; use `get_message_states_in_range` for historical
+/// backfill instead. Passing one returns an error rather than a misleadingly empty success.
+pub fn get_twaps_with_update_data(
+    states: &[MessageState],
+    price_ids: &[PriceIdentifier],
+    request_time: RequestTime,
+) -> Result<TwapsWithUpdateData> {
+    if matches!(request_time, RequestTime::Range { .. }) {
+        return Err(anyhow!(
+            "get_twaps_with_update_data does not support RequestTime::Range; use get_message_states_in_range instead"
+        ));
+    }
+
+    let selected_states = price_ids
+        .iter()
+        .filter_map(|price_id| {
+            let id = MessageIdentifier {
+                price_id: *price_id,
+                type_:    MessageType::TwapMessage,
+            };
+
+            let matching = states
+                .iter()
+                .filter(|state| state.id == id)
+                .filter(|state| match request_time {
+                    RequestTime::Latest => true,
+                    RequestTime::FirstAfter(timestamp) => state.publish_time >= timestamp,
+                    RequestTime::Range { .. } => unreachable!("rejected above"),
+                });
+
+            match request_time {
+                RequestTime::Latest => matching.max_by_key(|state| state.time()),
+                // The earliest point at/after the requested timestamp, not the newest overall.
+                RequestTime::FirstAfter(_) => matching.min_by_key(|state| state.time()),
+                RequestTime::Range { .. } => unreachable!("rejected above"),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let twaps = selected_states
+        .iter()
+        .filter_map(|state| match &state.message {
+            Message::TwapMessage(twap) => Some(*twap),
+            _ => None,
+        })
+        .collect();
+
+    let wormhole_merkle_update_data = construct_update_data(selected_states)?;
+
+    Ok(TwapsWithUpdateData {
+        twaps,
+        wormhole_merkle_update_data,
+    })
+}
+
+/// Returns every `MessageState` for `ids` whose `publish_time` falls within the requested range,
+/// each bundled with its own Wormhole Merkle update data so it can be verified independently of
+/// the other points in the response.
+///
+/// This lets a caller backfill a window of history in one round trip instead of polling
+/// `RequestTime::FirstAfter` one timestamp at a time.
+pub fn get_message_states_in_range(
+    states: &[MessageState],
+    ids: &[MessageIdentifier],
+    request_time: RequestTime,
+) -> Result<Vec<MessageStateWithUpdateData>> {
+    let RequestTime::Range { start, end } = request_time else {
+        return Err(anyhow!(
+            "get_message_states_in_range requires RequestTime::Range"
+        ));
+    };
+
+    let mut selected_states = ids
+        .iter()
+        .flat_map(|id| {
+            states
+                .iter()
+                .filter(move |state| &state.id == id)
+                .filter(|state| state.publish_time >= start && state.publish_time <= end)
+        })
+        .collect::<Vec<_>>();
+    selected_states.sort_by_key(|state| state.time());
+
+    selected_states
+        .into_iter()
+        .map(|state| {
+            Ok(MessageStateWithUpdateData {
+                state:                       state.clone(),
+                wormhole_merkle_update_data: construct_update_data(vec![state])?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::store::types::{
+            ProofSet,
+            Slot,
+            UnixTimestamp,
+        },
+        pyth_oracle::TwapMessage,
+    };
+
+    const PRICE_ID_A: [u8; 32] = [0xAA; 32];
+    const PRICE_ID_B: [u8; 32] = [0xBB; 32];
+
+    // The proof format itself is exercised in `types.rs`; these fixtures only need a
+    // placeholder that `construct_update_data` can round-trip for an empty/default proof.
+    fn twap_state(price_id: [u8; 32], publish_time: UnixTimestamp, slot: Slot) -> MessageState {
+        let message = Message::TwapMessage(TwapMessage {
+            id: price_id,
+            publish_time,
+            prev_publish_time: publish_time,
+            publish_slot: slot,
+            cumulative_price: 0,
+            cumulative_conf: 0,
+            num_down_slots: 0,
+            exponent: 0,
+        });
+
+        MessageState {
+            publish_time,
+            slot,
+            id: message.id(),
+            message,
+            raw_message: vec![],
+            proof_set: ProofSet {
+                wormhole_merkle_proof: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn get_twaps_latest_picks_the_newest_state_per_id() {
+        let states = vec![
+            twap_state(PRICE_ID_A, 100, 1),
+            twap_state(PRICE_ID_A, 200, 2),
+            twap_state(PRICE_ID_B, 150, 1),
+        ];
+
+        let result = get_twaps_with_update_data(
+            &states,
+            &[PriceIdentifier::new(PRICE_ID_A), PriceIdentifier::new(PRICE_ID_B)],
+            RequestTime::Latest,
+        )
+        .unwrap();
+
+        let publish_times: Vec<_> = result.twaps.iter().map(|twap| twap.publish_time).collect();
+        assert_eq!(publish_times, vec![200, 150]);
+    }
+
+    #[test]
+    fn get_twaps_first_after_picks_the_earliest_matching_state() {
+        let states = vec![
+            twap_state(PRICE_ID_A, 100, 1),
+            twap_state(PRICE_ID_A, 200, 2),
+            twap_state(PRICE_ID_A, 300, 3),
+        ];
+
+        let result = get_twaps_with_update_data(
+            &states,
+            &[PriceIdentifier::new(PRICE_ID_A)],
+            RequestTime::FirstAfter(150),
+        )
+        .unwrap();
+
+        assert_eq!(result.twaps.len(), 1);
+        assert_eq!(result.twaps[0].publish_time, 200);
+    }
+
+    #[test]
+    fn get_twaps_rejects_range_request_time() {
+        let states = vec![twap_state(PRICE_ID_A, 100, 1)];
+
+        let result = get_twaps_with_update_data(
+            &states,
+            &[PriceIdentifier::new(PRICE_ID_A)],
+            RequestTime::Range {
+                start: 0,
+                end:   200,
+            },
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn get_twaps_skips_ids_with_no_match() {
+        let states = vec![twap_state(PRICE_ID_A, 100, 1)];
+
+        let result = get_twaps_with_update_data(
+            &states,
+            &[PriceIdentifier::new(PRICE_ID_B)],
+            RequestTime::Latest,
+        )
+        .unwrap();
+
+        assert!(result.twaps.is_empty());
+    }
+
+    #[test]
+    fn get_twaps_breaks_ties_on_slot_via_message_time_ordering() {
+        let states = vec![
+            twap_state(PRICE_ID_A, 100, 1),
+            twap_state(PRICE_ID_A, 100, 2),
+        ];
+
+        let result = get_twaps_with_update_data(
+            &states,
+            &[PriceIdentifier::new(PRICE_ID_A)],
+            RequestTime::Latest,
+        )
+        .unwrap();
+
+        assert_eq!(result.twaps.len(), 1);
+        assert_eq!(result.twaps[0].publish_slot, 2);
+    }
+
+    #[test]
+    fn get_message_states_in_range_rejects_non_range_request_time() {
+        let states = vec![twap_state(PRICE_ID_A, 100, 1)];
+        let id = MessageIdentifier {
+            price_id: PriceIdentifier::new(PRICE_ID_A),
+            type_:    MessageType::TwapMessage,
+        };
+
+        assert!(get_message_states_in_range(&states, &[id], RequestTime::Latest).is_err());
+    }
+
+    #[test]
+    fn get_message_states_in_range_returns_every_point_in_window_sorted_by_time() {
+        let states = vec![
+            twap_state(PRICE_ID_A, 300, 3),
+            twap_state(PRICE_ID_A, 100, 1),
+            twap_state(PRICE_ID_A, 200, 2),
+            twap_state(PRICE_ID_A, 400, 4),
+        ];
+        let id = MessageIdentifier {
+            price_id: PriceIdentifier::new(PRICE_ID_A),
+            type_:    MessageType::TwapMessage,
+        };
+
+        let result = get_message_states_in_range(
+            &states,
+            &[id],
+            RequestTime::Range {
+                start: 100,
+                end:   300,
+            },
+        )
+        .unwrap();
+
+        let publish_times: Vec<_> = result.iter().map(|r| r.state.publish_time).collect();
+        assert_eq!(publish_times, vec![100, 200, 300]);
+    }
+
+    #[test]
+    fn get_message_states_in_range_with_start_after_end_is_empty() {
+        let states = vec![twap_state(PRICE_ID_A, 100, 1)];
+        let id = MessageIdentifier {
+            price_id: PriceIdentifier::new(PRICE_ID_A),
+            type_:    MessageType::TwapMessage,
+        };
+
+        let result = get_message_states_in_range(
+            &states,
+            &[id],
+            RequestTime::Range {
+                start: 300,
+                end:   100,
+            },
+        )
+        .unwrap();
+
+        assert!(result.is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+#[cfg(not(test))]
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH,
+};
+#[cfg(test)]
+use mock_instant::{
+    SystemTime,
+    UNIX_EPOCH,
+};
+use {
+    super::types::{
+        MessageIdentifier,
+        MessageState,
+        MessageTime,
+        UnixTimestamp,
+    },
+    std::{
+        collections::{
+            BTreeMap,
+            HashMap,
+        },
+        time::Duration,
+    },
+};
+
+/// Swapped for `mock_instant`'s clock under `#[cfg(test)]`, so the eviction passes below are
+/// deterministically testable instead of depending on wall-clock time.
+fn now() -> UnixTimestamp {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as UnixTimestamp
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CacheConfig {
+    /// How long a `MessageState` may sit in the cache past its `publish_time` before
+    /// `evict_expired` drops it.
+    pub ttl: Duration,
+
+    /// Once the cache's total size exceeds this, `evict_under_pressure` starts reclaiming space
+    /// even from entries that haven't expired yet.
+    pub high_water_mark_bytes: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(24 * 60 * 60),
+            high_water_mark_bytes: 512 * 1024 * 1024,
+        }
+    }
+}
+
+/// The in-memory `MessageState` store, keyed by `MessageIdentifier`/`MessageTime` like the rest
+/// of the store, with TTL expiry and priority-weighted eviction under memory pressure.
+pub struct MessageCache {
+    config:  CacheConfig,
+    entries: HashMap<MessageIdentifier, BTreeMap<MessageTime, MessageState>>,
+}
+
+impl MessageCache {
+    pub fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, state: MessageState) {
+        self.entries
+            .entry(state.id.clone())
+            .or_default()
+            .insert(state.time(), state);
+    }
+
+    /// Total size in bytes of the cached `MessageState`s' raw messages, used to decide whether a
+    /// priority-weighted eviction pass is needed.
+    pub fn size_bytes(&self) -> usize {
+        self.entries
+            .values()
+            .flat_map(|messages| messages.values())
+            .map(|state| state.raw_message.len())
+            .sum()
+    }
+
+    /// Drops every `MessageState` whose `publish_time` is older than `now - ttl`.
+    pub fn evict_expired(&mut self) {
+        let cutoff = now() - self.config.ttl.as_secs() as UnixTimestamp;
+        self.entries.retain(|_, messages| {
+            messages.retain(|time, _| time.publish_time >= cutoff);
+            !messages.is_empty()
+        });
+    }
+
+    /// Evicts entries by ascending `size_bytes * remaining_ttl` priority -- borrowing the
+    /// Whisper work-factor idea for cache retention -- until the cache is back under the
+    /// high-water mark. Small, nearly-expired entries are reclaimed first; large, fresh entries
+    /// are kept as long as possible.
+    pub fn evict_under_pressure(&mut self) {
+        let mut size = self.size_bytes();
+        if size <= self.config.high_water_mark_bytes {
+            return;
+        }
+
+        let now = now();
+        let ttl_secs = self.config.ttl.as_secs() as UnixTimestamp;
+        let mut candidates = self
+            .entries
+            .iter()
+            .flat_map(|(id, messages)| {
+                messages.iter().map(move |(time, state)| {
+                    let remaining_ttl = (ttl_secs - (now - time.publish_time)).max(0) as u64;
+                    let priority = state.raw_message.len() as u64 * remaining_ttl;
+                    (id.clone(), time.clone(), priority)
+                })
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(_, _, priority)| *priority);
+
+        for (id, time, _priority) in candidates {
+            if size <= self.config.high_water_mark_bytes {
+                break;
+            }
+            if let Some(messages) = self.entries.get_mut(&id) {
+                if let Some(state) = messages.remove(&time) {
+                    size -= state.raw_message.len();
+                }
+                if messages.is_empty() {
+                    self.entries.remove(&id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::store::types::{
+            MessageType,
+            ProofSet,
+        },
+        mock_instant::MockClock,
+        pyth_oracle::{
+            Message,
+            TwapMessage,
+        },
+        pyth_sdk::PriceIdentifier,
+    };
+
+    fn state(price_id: [u8; 32], publish_time: UnixTimestamp, raw_len: usize) -> MessageState {
+        let message = Message::TwapMessage(TwapMessage {
+            id: price_id,
+            publish_time,
+            prev_publish_time: publish_time,
+            publish_slot: 1,
+            cumulative_price: 0,
+            cumulative_conf: 0,
+            num_down_slots: 0,
+            exponent: 0,
+        });
+
+        MessageState {
+            publish_time,
+            slot: 1,
+            id: MessageIdentifier {
+                price_id: PriceIdentifier::new(price_id),
+                type_:    MessageType::TwapMessage,
+            },
+            message,
+            raw_message: vec![0u8; raw_len],
+            proof_set: ProofSet {
+                wormhole_merkle_proof: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn evict_expired_keeps_the_ttl_boundary_and_drops_older() {
+        MockClock::set_time(Duration::from_secs(1_000));
+        let mut cache = MessageCache::new(CacheConfig {
+            ttl: Duration::from_secs(100),
+            high_water_mark_bytes: usize::MAX,
+        });
+        // cutoff = now(1000) - ttl(100) = 900.
+        cache.insert(state([0xAA; 32], 899, 1)); // just before the cutoff -> evicted
+        cache.insert(state([0xBB; 32], 900, 1)); // exactly at the cutoff -> kept
+        cache.insert(state([0xCC; 32], 901, 1)); // just after the cutoff -> kept
+
+        cache.evict_expired();
+
+        assert_eq!(cache.size_bytes(), 2);
+    }
+
+    #[test]
+    fn evict_under_pressure_evicts_ascending_by_priority_and_stops_under_the_mark() {
+        MockClock::set_time(Duration::from_secs(1_000));
+        let mut cache = MessageCache::new(CacheConfig {
+            ttl: Duration::from_secs(1_000),
+            high_water_mark_bytes: 12,
+        });
+
+        // priority = size_bytes * remaining_ttl.
+        cache.insert(state([0x01; 32], 0, 500)); // remaining_ttl=0    -> priority 0      (evicted first)
+        cache.insert(state([0x02; 32], 1_000, 5)); // remaining_ttl=1000 -> priority 5,000  (evicted second)
+        cache.insert(state([0x03; 32], 1_000, 10)); // remaining_ttl=1000 -> priority 10,000 (kept)
+        assert_eq!(cache.size_bytes(), 515);
+
+        cache.evict_under_pressure();
+
+        // Stops as soon as it's under the high-water mark, even though the lowest-priority
+        // entry (by raw size alone) hasn't been touched.
+        assert_eq!(cache.size_bytes(), 10);
+    }
+}
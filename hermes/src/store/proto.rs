@@ -0,0 +1,5 @@
+//! Prost/tonic bindings generated from `proto/store/v1/message_state.proto` at build time.
+
+pub mod store_v1 {
+    include!(concat!(env!("OUT_DIR"), "/pyth.hermes.store.v1.rs"));
+}
@@ -0,0 +1,301 @@
+use {
+    super::{
+        proto::store_v1::{
+            self,
+            message_state_stream_server::MessageStateStream as MessageStateStreamService,
+            SubscribeRequest,
+        },
+        types::{
+            MessageIdentifier,
+            MessageState,
+            MessageType,
+        },
+    },
+    anyhow::Result,
+    futures::{
+        stream::select_all,
+        Stream,
+        StreamExt,
+    },
+    pyth_sdk::PriceIdentifier,
+    std::{
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        pin::Pin,
+        sync::{
+            Arc,
+            RwLock,
+        },
+    },
+    tokio::sync::broadcast,
+    tokio_stream::wrappers::BroadcastStream,
+    tonic::{
+        Request,
+        Response,
+        Status,
+    },
+};
+
+/// Bounds how many updates a lagging subscriber can fall behind before it starts missing them.
+const SUBSCRIBER_CHANNEL_CAPACITY: usize = 1_000;
+
+/// Fans newly stored `MessageState`s out to subscribers, each filtered down to the
+/// `MessageIdentifier`s they asked for, as a low-latency alternative to polling
+/// `get_price_feeds_with_update_data`.
+#[derive(Default)]
+pub struct MessageStateStream {
+    subscribers: RwLock<HashMap<MessageIdentifier, broadcast::Sender<store_v1::MessageState>>>,
+}
+
+impl MessageStateStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes to updates for `id`, returning a receiver that yields every future
+    /// `MessageState` stored for it.
+    pub fn subscribe(&self, id: MessageIdentifier) -> broadcast::Receiver<store_v1::MessageState> {
+        let mut subscribers = self.subscribers.write().unwrap();
+        subscribers
+            .entry(id)
+            .or_insert_with(|| broadcast::channel(SUBSCRIBER_CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Pushes a freshly stored `MessageState` to any subscribers registered for its id.
+    ///
+    /// If nobody is subscribed, the update is silently discarded rather than buffered. Entries
+    /// whose last receiver has dropped are pruned here so the subscriber map doesn't grow
+    /// unbounded across long-running streams. Returns an error if `state` can't be encoded onto
+    /// the wire, instead of publishing a `MessageState` with a silently-empty proof.
+    pub fn publish(&self, state: &MessageState) -> Result<()> {
+        let sender_state: store_v1::MessageState = state.try_into()?;
+
+        let sent = {
+            let subscribers = self.subscribers.read().unwrap();
+            match subscribers.get(&state.id) {
+                Some(sender) => {
+                    let _ = sender.send(sender_state);
+                    true
+                }
+                None => false,
+            }
+        };
+
+        if !sent {
+            return Ok(());
+        }
+
+        // Re-check under the write lock, in the same critical section as the removal: a
+        // concurrent `subscribe()` may have registered a new receiver for this id after we
+        // dropped the read lock above, and we must not delete the `Sender` out from under it.
+        let mut subscribers = self.subscribers.write().unwrap();
+        if subscribers
+            .get(&state.id)
+            .is_some_and(|sender| sender.receiver_count() == 0)
+        {
+            subscribers.remove(&state.id);
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&MessageState> for store_v1::MessageState {
+    type Error = anyhow::Error;
+
+    fn try_from(state: &MessageState) -> Result<Self> {
+        Ok(Self {
+            price_id: state.id.price_id.to_bytes().to_vec(),
+            message_type: store_v1::MessageType::from(&state.id.type_) as i32,
+            publish_time: Some(state.publish_time),
+            slot: state.slot,
+            raw_message: state.raw_message.clone(),
+            // The proof format is versioned independently of this schema (see
+            // `WormholePayload::try_from_bytes`), so it travels pre-serialized rather than as a
+            // nested message.
+            wormhole_merkle_proof: borsh::to_vec(&state.proof_set.wormhole_merkle_proof)?,
+        })
+    }
+}
+
+impl From<&MessageType> for store_v1::MessageType {
+    fn from(type_: &MessageType) -> Self {
+        match type_ {
+            // prost strips the enum's own name (`MESSAGE_TYPE_`) as a shared prefix from each
+            // proto3 enum value, so `MESSAGE_TYPE_PRICE_FEED` becomes the variant `PriceFeed`,
+            // not `MessageTypePriceFeed`.
+            MessageType::PriceFeedMessage => store_v1::MessageType::PriceFeed,
+            MessageType::TwapMessage => store_v1::MessageType::Twap,
+        }
+    }
+}
+
+impl TryFrom<store_v1::MessageType> for MessageType {
+    type Error = anyhow::Error;
+
+    fn try_from(type_: store_v1::MessageType) -> Result<Self> {
+        match type_ {
+            store_v1::MessageType::PriceFeed => Ok(MessageType::PriceFeedMessage),
+            store_v1::MessageType::Twap => Ok(MessageType::TwapMessage),
+            store_v1::MessageType::Unspecified => {
+                Err(anyhow::anyhow!("MessageType::Unspecified is not a valid message type"))
+            }
+        }
+    }
+}
+
+/// Adapts `MessageStateStream`'s broadcast-channel pub/sub to the `message_state.proto`
+/// `MessageStateStream` gRPC service, merging one subscription per requested
+/// `(price_id, message_type)` pair into a single outbound stream.
+pub struct MessageStateStreamServer {
+    stream: Arc<MessageStateStream>,
+}
+
+impl MessageStateStreamServer {
+    pub fn new(stream: Arc<MessageStateStream>) -> Self {
+        Self { stream }
+    }
+}
+
+#[tonic::async_trait]
+impl MessageStateStreamService for MessageStateStreamServer {
+    type SubscribeStream =
+        Pin<Box<dyn Stream<Item = Result<store_v1::MessageState, Status>> + Send + 'static>>;
+
+    async fn subscribe(
+        &self,
+        request: Request<SubscribeRequest>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let SubscribeRequest { price_ids, message_types } = request.into_inner();
+
+        // An empty `message_types` means "all of them", same as an unfiltered subscription.
+        let types = if message_types.is_empty() {
+            vec![MessageType::PriceFeedMessage, MessageType::TwapMessage]
+        } else {
+            message_types
+                .into_iter()
+                .map(|raw| {
+                    store_v1::MessageType::try_from(raw)
+                        .ok()
+                        .and_then(|type_| MessageType::try_from(type_).ok())
+                        .ok_or_else(|| Status::invalid_argument(format!("unknown message_type {raw}")))
+                })
+                .collect::<std::result::Result<Vec<_>, Status>>()?
+        };
+
+        if price_ids.is_empty() {
+            return Err(Status::invalid_argument("price_ids must not be empty"));
+        }
+
+        let price_ids = price_ids
+            .iter()
+            .map(|bytes| {
+                <[u8; 32]>::try_from(bytes.as_slice())
+                    .map(PriceIdentifier::new)
+                    .map_err(|_| Status::invalid_argument("price_id must be 32 bytes"))
+            })
+            .collect::<std::result::Result<Vec<_>, Status>>()?;
+
+        // Dedup so a client that repeats a price_id or message_type doesn't get every update
+        // delivered once per duplicate -- each (price_id, type) pair gets exactly one subscription.
+        let ids = price_ids
+            .into_iter()
+            .flat_map(|price_id| {
+                types.iter().cloned().map(move |type_| MessageIdentifier {
+                    price_id,
+                    type_,
+                })
+            })
+            .collect::<HashSet<_>>();
+
+        let receivers = ids.into_iter().map(|id| self.stream.subscribe(id)).collect::<Vec<_>>();
+
+        // A lagged receiver (see SUBSCRIBER_CHANNEL_CAPACITY) just resumes from the next
+        // available update, consistent with `publish()`'s no-buffering semantics -- this is a
+        // low-latency feed, not a gapless log.
+        let merged = select_all(receivers.into_iter().map(|receiver| {
+            BroadcastStream::new(receiver).filter_map(|item| async move { item.ok().map(Ok) })
+        }));
+
+        Ok(Response::new(Box::pin(merged)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::store::types::ProofSet,
+        pyth_oracle::{
+            Message,
+            TwapMessage,
+        },
+    };
+
+    fn state() -> MessageState {
+        let message = Message::TwapMessage(TwapMessage {
+            id: [0xAA; 32],
+            publish_time: 100,
+            prev_publish_time: 100,
+            publish_slot: 1,
+            cumulative_price: 0,
+            cumulative_conf: 0,
+            num_down_slots: 0,
+            exponent: 0,
+        });
+
+        MessageState {
+            publish_time: 100,
+            slot: 1,
+            id: MessageIdentifier {
+                price_id: PriceIdentifier::new([0xAA; 32]),
+                type_:    MessageType::TwapMessage,
+            },
+            message,
+            raw_message: vec![1, 2, 3],
+            proof_set: ProofSet {
+                wormhole_merkle_proof: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn message_type_round_trips_through_the_wire_enum() {
+        let state = state();
+
+        let proto_state: store_v1::MessageState = (&state).try_into().unwrap();
+        let proto_type = store_v1::MessageType::try_from(proto_state.message_type).unwrap();
+
+        assert_eq!(proto_type, store_v1::MessageType::Twap);
+        assert_eq!(MessageType::try_from(proto_type).unwrap(), state.id.type_);
+    }
+
+    #[tokio::test]
+    async fn subscribe_rejects_empty_price_ids() {
+        let server = MessageStateStreamServer::new(Arc::new(MessageStateStream::new()));
+
+        let result = server
+            .subscribe(Request::new(SubscribeRequest {
+                price_ids:     vec![],
+                message_types: vec![],
+            }))
+            .await;
+
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn message_state_round_trips_its_scalar_fields() {
+        let state = state();
+
+        let proto_state: store_v1::MessageState = (&state).try_into().unwrap();
+
+        assert_eq!(proto_state.price_id, state.id.price_id.to_bytes().to_vec());
+        assert_eq!(proto_state.publish_time, Some(state.publish_time));
+        assert_eq!(proto_state.slot, state.slot);
+        assert_eq!(proto_state.raw_message, state.raw_message);
+    }
+}
@@ -11,11 +11,18 @@ use {
     pyth_oracle::{
         Message,
         PriceFeedMessage,
+        TwapMessage,
     },
     pyth_sdk::PriceIdentifier,
     strum::EnumIter,
 };
 
+const ACCUMULATOR_UPDATE_MAGIC: u32 = 0x41555756u32;
+const ACCUMULATOR_UPDATE_MAJOR_VERSION: u8 = 1;
+
+/// The proof kinds that can appear in an `AccumulatorUpdateData` envelope, keyed by their wire
+/// discriminant. Wormhole-Merkle is the only kind that exists today, but the discriminant match
+/// in `WormholePayload::try_from_bytes` lets new kinds be added without touching call sites.
 #[derive(Clone, Debug, PartialEq)]
 pub enum WormholePayload {
     Merkle(WormholeMerkleProof),
@@ -23,36 +30,58 @@ pub enum WormholePayload {
 
 impl WormholePayload {
     pub fn try_from_bytes(bytes: &[u8], vaa_bytes: &[u8]) -> Result<Self> {
-        if bytes.len() != 37 {
-            return Err(anyhow!("Invalid message length"));
-        }
+        let cursor = &mut &bytes[..];
 
         // TODO: Use byte string literals for this check
-        let magic = u32::from_be_bytes(bytes[0..4].try_into()?);
-        if magic != 0x41555756u32 {
+        let magic = u32::from_be_bytes(take(cursor)?);
+        if magic != ACCUMULATOR_UPDATE_MAGIC {
             return Err(anyhow!("Invalid magic"));
         }
 
-        let message_type = u8::from_be_bytes(bytes[4..5].try_into()?);
-
-        if message_type != 0 {
-            return Err(anyhow!("Invalid message type"));
+        let [major_version] = take(cursor)?;
+        if major_version != ACCUMULATOR_UPDATE_MAJOR_VERSION {
+            return Err(anyhow!("Unsupported major version: {major_version}"));
         }
 
-        let slot = u64::from_be_bytes(bytes[5..13].try_into()?);
-        let ring_size = u32::from_be_bytes(bytes[13..17].try_into()?);
-        let root_digest = bytes[17..37].try_into()?;
+        // Minor version bumps are allowed to add trailing bytes we don't understand yet; the
+        // length-prefixed `trailing` vector below lets us skip them instead of erroring out.
+        let [_minor_version] = take(cursor)?;
 
+        let [trailing_len] = take(cursor)?;
+        let trailing_len = trailing_len as usize;
+        if cursor.len() < trailing_len {
+            return Err(anyhow!("Invalid trailing length"));
+        }
+        *cursor = &cursor[trailing_len..];
 
-        Ok(Self::Merkle(WormholeMerkleProof {
-            root: root_digest,
-            slot,
-            ring_size,
-            vaa: vaa_bytes.to_vec(),
-        }))
+        let [proof_type] = take(cursor)?;
+        match proof_type {
+            0 => {
+                let slot = u64::from_be_bytes(take(cursor)?);
+                let ring_size = u32::from_be_bytes(take(cursor)?);
+                let root_digest = take(cursor)?;
+
+                Ok(Self::Merkle(WormholeMerkleProof {
+                    root: root_digest,
+                    slot,
+                    ring_size,
+                    vaa: vaa_bytes.to_vec(),
+                }))
+            }
+            _ => Err(anyhow!("Unknown proof type: {proof_type}")),
+        }
     }
 }
 
+/// Reads and consumes the next `N` bytes from `cursor`, advancing it past them.
+fn take<const N: usize>(cursor: &mut &[u8]) -> Result<[u8; N]> {
+    if cursor.len() < N {
+        return Err(anyhow!("Invalid message length"));
+    }
+    let (head, tail) = cursor.split_at(N);
+    *cursor = tail;
+    head.try_into().map_err(|_| anyhow!("Invalid message length"))
+}
 
 // TODO: We can use strum on Message enum to derive this.
 #[derive(Clone, Debug, Eq, PartialEq, Hash, EnumIter)]
@@ -161,6 +190,10 @@ pub type UnixTimestamp = i64;
 pub enum RequestTime {
     Latest,
     FirstAfter(UnixTimestamp),
+    Range {
+        start: UnixTimestamp,
+        end:   UnixTimestamp,
+    },
 }
 
 #[derive(Clone, PartialEq, Debug, BorshDeserialize)]
@@ -185,4 +218,113 @@ pub enum Update {
 pub struct PriceFeedsWithUpdateData {
     pub price_feeds:                 Vec<PriceFeedMessage>,
     pub wormhole_merkle_update_data: Vec<Vec<u8>>,
-}
\ No newline at end of file
+}
+
+pub struct TwapsWithUpdateData {
+    pub twaps:                       Vec<TwapMessage>,
+    pub wormhole_merkle_update_data: Vec<Vec<u8>>,
+}
+
+/// A single historical `MessageState`, bundled with the Wormhole Merkle update data that proves
+/// it on its own, independent of any other point returned alongside it.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MessageStateWithUpdateData {
+    pub state:                       MessageState,
+    pub wormhole_merkle_update_data: Vec<Vec<u8>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VAA_BYTES: &[u8] = b"some-vaa";
+
+    fn merkle_payload(major_version: u8, minor_version: u8, trailing: &[u8]) -> Vec<u8> {
+        let mut bytes = ACCUMULATOR_UPDATE_MAGIC.to_be_bytes().to_vec();
+        bytes.push(major_version);
+        bytes.push(minor_version);
+        bytes.push(trailing.len() as u8);
+        bytes.extend_from_slice(trailing);
+        bytes.push(0); // Wormhole-Merkle proof discriminant
+        bytes.extend_from_slice(&42u64.to_be_bytes()); // slot
+        bytes.extend_from_slice(&7u32.to_be_bytes()); // ring_size
+        bytes.extend_from_slice(&[0xAB; 20]); // root digest
+        bytes
+    }
+
+    #[test]
+    fn parses_a_well_formed_merkle_payload() {
+        let bytes = merkle_payload(ACCUMULATOR_UPDATE_MAJOR_VERSION, 0, &[]);
+
+        let payload = WormholePayload::try_from_bytes(&bytes, VAA_BYTES).unwrap();
+
+        assert_eq!(
+            payload,
+            WormholePayload::Merkle(WormholeMerkleProof {
+                root:      [0xAB; 20],
+                slot:      42,
+                ring_size: 7,
+                vaa:       VAA_BYTES.to_vec(),
+            })
+        );
+    }
+
+    #[test]
+    fn skips_unknown_minor_version_trailing_bytes() {
+        let bytes = merkle_payload(ACCUMULATOR_UPDATE_MAJOR_VERSION, 99, &[1, 2, 3]);
+
+        let payload = WormholePayload::try_from_bytes(&bytes, VAA_BYTES).unwrap();
+
+        assert_eq!(
+            payload,
+            WormholePayload::Merkle(WormholeMerkleProof {
+                root:      [0xAB; 20],
+                slot:      42,
+                ring_size: 7,
+                vaa:       VAA_BYTES.to_vec(),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_magic() {
+        let mut bytes = merkle_payload(ACCUMULATOR_UPDATE_MAJOR_VERSION, 0, &[]);
+        bytes[0] ^= 0xFF;
+
+        assert!(WormholePayload::try_from_bytes(&bytes, VAA_BYTES).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_major_version() {
+        let bytes = merkle_payload(ACCUMULATOR_UPDATE_MAJOR_VERSION + 1, 0, &[]);
+
+        assert!(WormholePayload::try_from_bytes(&bytes, VAA_BYTES).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_trailing_section() {
+        let mut bytes = merkle_payload(ACCUMULATOR_UPDATE_MAJOR_VERSION, 0, &[]);
+        // Claim 5 trailing bytes are present when none are.
+        let trailing_len_index = 6;
+        bytes[trailing_len_index] = 5;
+
+        assert!(WormholePayload::try_from_bytes(&bytes, VAA_BYTES).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_proof_type() {
+        let mut bytes = merkle_payload(ACCUMULATOR_UPDATE_MAJOR_VERSION, 0, &[]);
+        let proof_type_index = 7;
+        bytes[proof_type_index] = 1;
+
+        assert!(WormholePayload::try_from_bytes(&bytes, VAA_BYTES).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_proof_body() {
+        let bytes = merkle_payload(ACCUMULATOR_UPDATE_MAJOR_VERSION, 0, &[]);
+        let truncated = &bytes[..bytes.len() - 1];
+
+        assert!(WormholePayload::try_from_bytes(truncated, VAA_BYTES).is_err());
+    }
+}